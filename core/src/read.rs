@@ -0,0 +1,342 @@
+use std::io;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::{FileInfo, SERVER_WAIT_TIME};
+
+/// A de-interleaved block of audio sample data, one `Vec<T>` per channel.
+pub struct AudioBlock<T: Copy + Clone + Default + Send> {
+    /// The sample data, one vector per channel.
+    pub channels: Vec<Vec<T>>,
+}
+
+impl<T: Copy + Clone + Default + Send> AudioBlock<T> {
+    /// Create a new block with `num_channels` channels, each holding
+    /// `block_frames` frames initialized to the default value.
+    pub fn new(num_channels: usize, block_frames: usize) -> Self {
+        Self {
+            channels: (0..num_channels)
+                .map(|_| vec![T::default(); block_frames])
+                .collect(),
+        }
+    }
+}
+
+/// A block of audio data read from a [`ReadDiskStream`], along with the
+/// frame in the file/stream at which it starts.
+pub struct DataBlock<T: Copy + Clone + Default + Send> {
+    /// The decoded audio data.
+    pub block: AudioBlock<T>,
+    /// The frame in the file/stream at which this block starts.
+    pub start_frame: usize,
+}
+
+/// How precisely a seek should land on the requested frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeekMode {
+    /// Seek exactly to the requested frame. On compressed formats this may
+    /// require decoding (and discarding) samples between the nearest packet
+    /// boundary and the requested frame.
+    #[default]
+    Accurate,
+    /// Seek to the nearest convenient boundary at or before the requested
+    /// frame (e.g. a packet boundary) without decoding up to the exact
+    /// sample. Much cheaper than [`SeekMode::Accurate`], at the cost of the
+    /// landed frame possibly not matching the one requested; the actual
+    /// landed frame is reported back by [`Decoder::seek`].
+    Coarse,
+}
+
+/// User-configurable options for opening a [`ReadDiskStream`].
+#[derive(Clone)]
+pub struct ReadStreamOptions<D: Decoder> {
+    /// The number of frames in each block read from the decoder.
+    pub block_frames: usize,
+    /// The number of blocks to keep cached behind the playhead.
+    pub num_cache_blocks: usize,
+    /// The number of blocks to decode ahead of the playhead.
+    pub num_look_ahead_blocks: usize,
+    /// Additional options specific to the `Decoder` implementation.
+    pub additional_opts: D::AdditionalOpts,
+}
+
+impl<D: Decoder> Default for ReadStreamOptions<D> {
+    fn default() -> Self {
+        Self {
+            block_frames: D::DEFAULT_BLOCK_FRAMES,
+            num_cache_blocks: D::DEFAULT_NUM_CACHE_BLOCKS,
+            num_look_ahead_blocks: D::DEFAULT_NUM_LOOK_AHEAD_BLOCKS,
+            additional_opts: Default::default(),
+        }
+    }
+}
+
+/// A decoder that can be driven by a [`ReadDiskStream`].
+///
+/// Implementations decode a single track of audio, and are polled from a
+/// background thread so that playback never blocks on disk or network I/O.
+pub trait Decoder: Sized + Send + 'static {
+    /// The sample type this decoder produces, e.g. `f32` or `i16`.
+    type T: Copy + Clone + Default + Send + 'static;
+    /// Additional per-file information returned alongside [`FileInfo`].
+    type FileParams: Send + Clone;
+    /// The error type returned when a file/stream fails to open.
+    type OpenError: From<io::Error> + Send;
+    /// The error type returned for unrecoverable errors encountered while decoding.
+    type FatalError: Send;
+    /// Decoder-specific options passed through [`ReadStreamOptions`].
+    type AdditionalOpts: Send + Clone + Default;
+
+    /// The default number of frames in each block.
+    const DEFAULT_BLOCK_FRAMES: usize;
+    /// The default number of cache blocks kept behind the playhead.
+    const DEFAULT_NUM_CACHE_BLOCKS: usize;
+    /// The default number of blocks to read ahead of the playhead.
+    const DEFAULT_NUM_LOOK_AHEAD_BLOCKS: usize;
+    /// The default interval at which the background thread polls for work.
+    const DEFAULT_POLL_INTERVAL: Duration;
+
+    /// Open the file at `file` and prepare to decode starting at `start_frame`.
+    fn new(
+        file: PathBuf,
+        start_frame: usize,
+        block_frames: usize,
+        poll_interval: Duration,
+        additional_opts: Self::AdditionalOpts,
+    ) -> Result<(Self, FileInfo<Self::FileParams>), Self::OpenError>;
+
+    /// Seek towards `frame` using `mode`, returning the frame actually landed
+    /// on (`frame` itself for [`SeekMode::Accurate`], unless `frame` lies past
+    /// the end of the stream).
+    fn seek(&mut self, frame: usize, mode: SeekMode) -> Result<usize, Self::FatalError>;
+
+    /// Decode the next `block.channels[..].len()` frames into `block`.
+    fn decode(&mut self, block: &mut AudioBlock<Self::T>) -> Result<(), Self::FatalError>;
+
+    /// The current playhead position, in frames.
+    fn playhead_frame(&self) -> usize;
+
+    /// The decoder's current total frame count.
+    ///
+    /// This may be more precise than the `num_frames` a caller already has
+    /// from the [`FileInfo`] returned by [`Decoder::new`]: some decoders only
+    /// estimate the total up front and correct it once [`Decoder::decode`]
+    /// reaches the real end of the stream. [`ReadDiskStream::info`] reflects
+    /// that correction after it happens.
+    fn num_frames(&self) -> usize;
+}
+
+/// An error returned while reading from a [`ReadDiskStream`].
+#[derive(Debug)]
+pub enum ReadError<E> {
+    /// The decoder returned a fatal error.
+    Fatal(E),
+    /// The background server thread is no longer running.
+    ServerClosed,
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for ReadError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadError::Fatal(e) => write!(f, "{e}"),
+            ReadError::ServerClosed => write!(f, "the disk stream server thread has stopped"),
+        }
+    }
+}
+
+impl<E: std::fmt::Debug + std::fmt::Display> std::error::Error for ReadError<E> {}
+
+enum ServerRequest<D: Decoder> {
+    ReadInto(AudioBlock<D::T>),
+    Seek(usize, SeekMode),
+    Shutdown,
+}
+
+enum ServerResponse<D: Decoder> {
+    Block(AudioBlock<D::T>, usize, usize),
+    SeekResult(Result<usize, D::FatalError>),
+}
+
+fn run_server<D: Decoder>(
+    mut decoder: D,
+    to_server_rx: Receiver<ServerRequest<D>>,
+    from_server_tx: Sender<ServerResponse<D>>,
+    poll_interval: Duration,
+) {
+    loop {
+        match to_server_rx.recv_timeout(poll_interval) {
+            Ok(ServerRequest::ReadInto(mut block)) => {
+                let start_frame = decoder.playhead_frame();
+                let response = match decoder.decode(&mut block) {
+                    Ok(()) => ServerResponse::Block(block, start_frame, decoder.num_frames()),
+                    Err(_) => break,
+                };
+                if from_server_tx.send(response).is_err() {
+                    break;
+                }
+            }
+            Ok(ServerRequest::Seek(frame, mode)) => {
+                let result = decoder.seek(frame, mode);
+                let is_err = result.is_err();
+                if from_server_tx.send(ServerResponse::SeekResult(result)).is_err() || is_err {
+                    break;
+                }
+            }
+            Ok(ServerRequest::Shutdown) => break,
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+}
+
+/// A stream that reads and decodes audio from disk on a background thread,
+/// keeping a look-ahead buffer so the playback thread never blocks on I/O.
+pub struct ReadDiskStream<D: Decoder> {
+    to_server_tx: Sender<ServerRequest<D>>,
+    from_server_rx: Receiver<ServerResponse<D>>,
+    server_thread: Option<JoinHandle<()>>,
+
+    current_block: Option<DataBlock<D::T>>,
+    num_channels: usize,
+    block_frames: usize,
+
+    file_info: FileInfo<D::FileParams>,
+}
+
+impl<D: Decoder> ReadDiskStream<D> {
+    /// Open `file` and start decoding it from `start_frame` on a background
+    /// thread.
+    pub fn new(
+        file: PathBuf,
+        start_frame: usize,
+        options: ReadStreamOptions<D>,
+    ) -> Result<Self, D::OpenError> {
+        let (decoder, file_info) = D::new(
+            file,
+            start_frame,
+            options.block_frames,
+            D::DEFAULT_POLL_INTERVAL,
+            options.additional_opts.clone(),
+        )?;
+
+        Ok(Self::from_decoder(
+            decoder,
+            file_info,
+            D::DEFAULT_POLL_INTERVAL,
+            options,
+        ))
+    }
+
+    /// Wrap an already-opened `decoder` in a disk stream, without going
+    /// through [`Decoder::new`]. Useful for decoders opened from a source
+    /// other than a file path (e.g. `SymphoniaDecoder::from_source`).
+    pub fn from_decoder(
+        decoder: D,
+        file_info: FileInfo<D::FileParams>,
+        poll_interval: Duration,
+        options: ReadStreamOptions<D>,
+    ) -> Self {
+        let (to_server_tx, to_server_rx) = mpsc::channel();
+        let (from_server_tx, from_server_rx) = mpsc::channel();
+
+        let server_thread = thread::spawn(move || {
+            run_server(decoder, to_server_rx, from_server_tx, poll_interval);
+        });
+
+        let num_channels = file_info.num_channels as usize;
+        let block_frames = options.block_frames;
+
+        let mut stream = Self {
+            to_server_tx,
+            from_server_rx,
+            server_thread: Some(server_thread),
+
+            current_block: None,
+            num_channels,
+            block_frames,
+
+            file_info,
+        };
+
+        for _ in 0..options.num_look_ahead_blocks.max(1) {
+            stream.request_block();
+        }
+
+        stream
+    }
+
+    fn request_block(&self) {
+        let block = AudioBlock::new(self.num_channels, self.block_frames);
+        let _ = self.to_server_tx.send(ServerRequest::ReadInto(block));
+    }
+
+    /// Block until the next block of `self.block_frames` frames is ready,
+    /// then return it.
+    pub fn read(&mut self) -> Result<&DataBlock<D::T>, ReadError<D::FatalError>> {
+        loop {
+            match self
+                .from_server_rx
+                .recv_timeout(SERVER_WAIT_TIME)
+            {
+                Ok(ServerResponse::Block(block, start_frame, num_frames)) => {
+                    // The decoder may have corrected an estimated frame count
+                    // upon reaching the real end of the stream; keep `info()`
+                    // in sync with it.
+                    self.file_info.num_frames = num_frames;
+
+                    self.current_block = Some(DataBlock { block, start_frame });
+                    self.request_block();
+                    return Ok(self.current_block.as_ref().unwrap());
+                }
+                Ok(ServerResponse::SeekResult(_)) => continue,
+                Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Err(ReadError::ServerClosed),
+            }
+        }
+    }
+
+    /// Seek the stream towards `frame` using `mode`, discarding any cached
+    /// look-ahead blocks. Returns the frame actually landed on, which may
+    /// differ from `frame` when `mode` is [`SeekMode::Coarse`] or `frame`
+    /// lies past the end of the stream.
+    pub fn seek(&mut self, frame: usize, mode: SeekMode) -> Result<usize, ReadError<D::FatalError>> {
+        self.to_server_tx
+            .send(ServerRequest::Seek(frame, mode))
+            .map_err(|_| ReadError::ServerClosed)?;
+
+        let mut num_discarded = 0;
+        loop {
+            match self.from_server_rx.recv_timeout(SERVER_WAIT_TIME) {
+                Ok(ServerResponse::SeekResult(result)) => {
+                    // Refill the look-ahead buffer, now from the landed frame.
+                    for _ in 0..num_discarded {
+                        self.request_block();
+                    }
+                    return result.map_err(ReadError::Fatal);
+                }
+                Ok(ServerResponse::Block(..)) => {
+                    num_discarded += 1;
+                    continue;
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Err(ReadError::ServerClosed),
+            }
+        }
+    }
+
+    /// Info about the file/stream being read.
+    pub fn info(&self) -> &FileInfo<D::FileParams> {
+        &self.file_info
+    }
+}
+
+impl<D: Decoder> Drop for ReadDiskStream<D> {
+    fn drop(&mut self) {
+        let _ = self.to_server_tx.send(ServerRequest::Shutdown);
+        if let Some(handle) = self.server_thread.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use std::fmt;
+use std::io;
+
+use symphonia::core::errors::Error as SymphoniaError;
+
+/// An error that can occur when opening a file/stream with [`crate::SymphoniaDecoder`].
+#[derive(Debug)]
+pub enum OpenError {
+    /// An IO error occurred while opening the file.
+    Io(io::Error),
+    /// Symphonia could not probe/decode the given source.
+    Symphonia(SymphoniaError),
+    /// The format reader did not report a default track.
+    NoDefaultTrack,
+    /// The number of channels could not be determined from the codec
+    /// parameters or the first decoded packet.
+    NoNumChannels,
+}
+
+impl fmt::Display for OpenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpenError::Io(e) => write!(f, "failed to open file: {e}"),
+            OpenError::Symphonia(e) => write!(f, "symphonia error: {e}"),
+            OpenError::NoDefaultTrack => write!(f, "the format reader has no default track"),
+            OpenError::NoNumChannels => {
+                write!(f, "could not determine the number of channels in the track")
+            }
+        }
+    }
+}
+
+impl std::error::Error for OpenError {}
+
+impl From<io::Error> for OpenError {
+    fn from(e: io::Error) -> Self {
+        OpenError::Io(e)
+    }
+}
+
+impl From<SymphoniaError> for OpenError {
+    fn from(e: SymphoniaError) -> Self {
+        OpenError::Symphonia(e)
+    }
+}
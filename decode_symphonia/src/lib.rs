@@ -11,24 +11,51 @@ use std::time::Duration;
 use log::debug;
 use symphonia::core::audio::AudioBuffer;
 use symphonia::core::codecs::{CodecParameters, Decoder as SymphDecoder, DecoderOptions};
+use symphonia::core::conv::ConvertibleSample;
 use symphonia::core::errors::Error;
-use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
-use symphonia::core::io::MediaSourceStream;
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode as SymphSeekMode, SeekTo};
+use symphonia::core::io::{MediaSource, MediaSourceStream};
 use symphonia::core::meta::{Metadata, MetadataOptions, MetadataRevision};
 use symphonia::core::probe::Hint;
 
 use creek_core::read::Decoder;
-use creek_core::{AudioBlock, FileInfo};
+use creek_core::{AudioBlock, FileInfo, SeekMode};
 
 mod error;
 pub use error::OpenError;
 
+/// Look up `track_id`'s current codec parameters on `reader`, falling back to
+/// `fallback` if the track can't be found.
+///
+/// Used to rebuild the decoder on `Error::ResetRequired`, which Symphonia
+/// raises when the track/stream parameters change mid-stream: rebuilding
+/// from `fallback` (the old, about-to-be-replaced decoder's cached params)
+/// would just reconstruct an identical decoder and re-raise
+/// `ResetRequired` forever, so the reader's current params must be used
+/// instead whenever they're available.
+fn refresh_codec_params<'a>(
+    reader: &'a dyn FormatReader,
+    track_id: u32,
+    fallback: &'a CodecParameters,
+) -> &'a CodecParameters {
+    reader
+        .tracks()
+        .iter()
+        .find(|track| track.id == track_id)
+        .map(|track| &track.codec_params)
+        .unwrap_or(fallback)
+}
+
 /// A decoder for creek that reads from an audio file.
-pub struct SymphoniaDecoder {
+///
+/// Generic over the output sample type `S`, e.g. `f32` (the default) or
+/// `i16`, so callers that only need fixed-point samples don't pay for
+/// Symphonia's 32-bit float decode buffers.
+pub struct SymphoniaDecoder<S: ConvertibleSample = f32> {
     reader: Box<dyn FormatReader>,
     decoder: Box<dyn SymphDecoder>,
 
-    decode_buffer: AudioBuffer<f32>,
+    decode_buffer: AudioBuffer<S>,
     decode_buffer_len: usize,
     curr_decode_buffer_frame: usize,
 
@@ -40,14 +67,26 @@ pub struct SymphoniaDecoder {
 
     seek_delta: usize,
     default_track_id: u32,
+
+    /// Leading encoder delay frames skipped for gapless playback.
+    delay: usize,
 }
 
-impl Decoder for SymphoniaDecoder {
-    type T = f32;
+/// Options specific to [`SymphoniaDecoder`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SymphoniaDecoderOpts {
+    /// When `true`, trim the track's leading encoder delay and trailing
+    /// padding (as reported by Symphonia's codec parameters) so back-to-back
+    /// files and looped one-shots don't click at the seams.
+    pub gapless: bool,
+}
+
+impl<S: ConvertibleSample + Copy + Clone + Default + Send + 'static> Decoder for SymphoniaDecoder<S> {
+    type T = S;
     type FileParams = SymphoniaDecoderInfo;
     type OpenError = OpenError;
     type FatalError = Error;
-    type AdditionalOpts = ();
+    type AdditionalOpts = SymphoniaDecoderOpts;
 
     const DEFAULT_BLOCK_FRAMES: usize = 16384;
     const DEFAULT_NUM_CACHE_BLOCKS: usize = 0;
@@ -58,8 +97,8 @@ impl Decoder for SymphoniaDecoder {
         file: PathBuf,
         start_frame: usize,
         block_frames: usize,
-        _poll_interval: Duration,
-        _additional_opts: Self::AdditionalOpts,
+        poll_interval: Duration,
+        additional_opts: Self::AdditionalOpts,
     ) -> Result<(Self, FileInfo<Self::FileParams>), Self::OpenError> {
         // Create a hint to help the format registry guess what format reader is appropriate.
         let mut hint = Hint::new();
@@ -73,6 +112,256 @@ impl Decoder for SymphoniaDecoder {
 
         let source = Box::new(File::open(file)?);
 
+        Self::from_source(
+            source,
+            Some(hint),
+            start_frame,
+            block_frames,
+            poll_interval,
+            additional_opts,
+        )
+    }
+
+    fn seek(&mut self, frame: usize, mode: SeekMode) -> Result<usize, Self::FatalError> {
+        if frame >= self.num_frames {
+            // Do nothing if out of range.
+            self.playhead_frame = self.num_frames;
+
+            return Ok(self.playhead_frame);
+        }
+
+        // Offset by the leading encoder delay so `frame` stays relative to
+        // the first audible frame, not the raw (undelayed) stream.
+        let target_raw_frame = frame + self.delay;
+
+        let res = self.reader.seek(
+            match mode {
+                SeekMode::Accurate => SymphSeekMode::Accurate,
+                SeekMode::Coarse => SymphSeekMode::Coarse,
+            },
+            SeekTo::TimeStamp {
+                ts: target_raw_frame as u64,
+                track_id: self.default_track_id,
+            },
+        )?;
+
+        match mode {
+            SeekMode::Accurate => {
+                self.seek_delta = target_raw_frame - res.actual_ts as usize;
+                if self.seek_delta > 0 {
+                    debug!("Found seek delta of {}", self.seek_delta);
+                }
+                self.playhead_frame = frame;
+            }
+            SeekMode::Coarse => {
+                // Don't bother decoding-and-discarding back up to the exact
+                // sample; just land wherever the nearest packet boundary is
+                // and report that back to the caller.
+                self.seek_delta = 0;
+                self.playhead_frame = (res.actual_ts as usize)
+                    .saturating_sub(self.delay)
+                    .min(self.num_frames);
+            }
+        }
+
+        self.reset_decode_buffer = true;
+        self.curr_decode_buffer_frame = 0;
+
+        // Discard any state left over from decoding before the seek point
+        // (e.g. the MP3 bit reservoir, AAC/ALAC overlap-add history), so the
+        // first packet decoded after the seek doesn't glitch.
+        self.decoder.reset();
+
+        Ok(self.playhead_frame)
+    }
+
+    fn decode(&mut self, block: &mut AudioBlock<Self::T>) -> Result<(), Self::FatalError> {
+        if self.playhead_frame >= self.num_frames {
+            // Fill with zeros if reached the end of the file.
+            for ch in block.channels.iter_mut() {
+                ch.fill(Default::default());
+            }
+
+            return Ok(());
+        }
+
+        let mut reached_end_of_file = false;
+        let start_playhead_frame = self.playhead_frame;
+
+        let mut block_start_frame = 0;
+        while block_start_frame < self.block_frames {
+            // Stop at the (possibly gapless-trimmed) end of the stream even
+            // though the decoder may still have real, decodable packets left
+            // (e.g. trailing encoder padding): treat it exactly like the real
+            // EOF below so it doesn't get copied into the block as audio.
+            let frames_until_num_frames =
+                self.num_frames - (start_playhead_frame + block_start_frame);
+            if frames_until_num_frames == 0 {
+                reached_end_of_file = true;
+                break;
+            }
+
+            let num_frames_to_cpy = if self.reset_decode_buffer {
+                // Get new data first.
+                self.reset_decode_buffer = false;
+                0
+            } else {
+                // Find the maximum amount of frames that can be copied.
+                (self.block_frames - block_start_frame)
+                    .min(self.decode_buffer_len - self.curr_decode_buffer_frame)
+                    .min(frames_until_num_frames)
+            };
+
+            if num_frames_to_cpy != 0 {
+                let src_planes = self.decode_buffer.planes();
+                let src_channels = src_planes.planes();
+
+                for (dst_ch, src_ch) in block.channels.iter_mut().zip(src_channels) {
+                    let src_ch_part = &src_ch[self.curr_decode_buffer_frame
+                        ..self.curr_decode_buffer_frame + num_frames_to_cpy];
+                    dst_ch[block_start_frame..block_start_frame + num_frames_to_cpy]
+                        .copy_from_slice(src_ch_part);
+                }
+
+                block_start_frame += num_frames_to_cpy;
+
+                self.curr_decode_buffer_frame += num_frames_to_cpy;
+                if self.curr_decode_buffer_frame >= self.decode_buffer_len {
+                    self.reset_decode_buffer = true;
+                }
+            } else {
+                // Decode the next packet.
+
+                'fetch: loop {
+                    match self.reader.next_packet() {
+                        Ok(packet) => {
+                            // Retry the same packet on `ResetRequired` instead of
+                            // fetching a new one, since the decoder that produced
+                            // the error never saw it.
+                            loop {
+                                match self.decoder.decode(&packet) {
+                                    Ok(decoded) => {
+                                        let seek_delta = self.seek_delta;
+                                        let decoded_frames = decoded.frames();
+                                        if seek_delta < decoded_frames {
+                                            self.seek_delta = 0;
+                                            self.decode_buffer_len = decoded_frames;
+                                            decoded.convert(&mut self.decode_buffer);
+
+                                            self.curr_decode_buffer_frame = seek_delta;
+                                            if seek_delta > 0 {
+                                                debug!("Recovered seek delta of {seek_delta}");
+                                            }
+                                        } else {
+                                            // Continue until we decode back to the desired seek point
+                                            self.seek_delta -= decoded_frames;
+                                            debug!(
+                                                "Skipped {} decoded frames, seek delta is now {}",
+                                                decoded_frames, self.seek_delta
+                                            );
+                                            continue 'fetch;
+                                        }
+                                        break 'fetch;
+                                    }
+                                    Err(Error::DecodeError(err)) => {
+                                        // Decode errors are not fatal.
+                                        log::warn!("{err}");
+                                        // Continue by decoding the next packet.
+                                        continue 'fetch;
+                                    }
+                                    Err(Error::ResetRequired) => {
+                                        // The track/stream parameters changed mid-stream;
+                                        // get a fresh decoder and retry this packet.
+                                        debug!("Decoder reset required, recreating decoder");
+                                        let params = refresh_codec_params(
+                                            self.reader.as_ref(),
+                                            self.default_track_id,
+                                            self.decoder.codec_params(),
+                                        );
+                                        self.decoder = symphonia::default::get_codecs()
+                                            .make(params, &DecoderOptions::default())?;
+                                        continue;
+                                    }
+                                    Err(e) => {
+                                        // Errors other than decode errors are fatal.
+                                        return Err(e);
+                                    }
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            if let Error::IoError(io_error) = &e {
+                                if io_error.kind() == std::io::ErrorKind::UnexpectedEof {
+                                    // End of file, stop decoding. If `num_frames` was
+                                    // only an estimate, it may have overshot the real
+                                    // end of the stream; clamp it to what we actually
+                                    // decoded so future calls don't try to read past
+                                    // it again.
+                                    reached_end_of_file = true;
+                                    self.num_frames = self
+                                        .num_frames
+                                        .min(start_playhead_frame + block_start_frame);
+                                    block_start_frame = self.block_frames;
+                                    break;
+                                } else {
+                                    return Err(e);
+                                }
+                            } else {
+                                return Err(e);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if reached_end_of_file {
+            self.playhead_frame = self.num_frames;
+        } else {
+            self.playhead_frame += self.block_frames;
+        }
+
+        Ok(())
+    }
+
+    fn playhead_frame(&self) -> usize {
+        self.playhead_frame
+    }
+
+    fn num_frames(&self) -> usize {
+        self.num_frames
+    }
+}
+
+impl<S: ConvertibleSample> Drop for SymphoniaDecoder<S> {
+    fn drop(&mut self) {
+        let _ = self.decoder.finalize();
+    }
+}
+
+impl<S: ConvertibleSample + Copy + Clone + Default + Send + 'static> SymphoniaDecoder<S> {
+    /// Construct a decoder from an arbitrary [`MediaSource`] rather than a
+    /// file on disk, e.g. an in-memory buffer (`Cursor<Vec<u8>>`) or a
+    /// network-backed reader.
+    ///
+    /// `hint` may carry a file extension or MIME type to help Symphonia's
+    /// format probe pick the right demuxer, since a non-file source has no
+    /// path to infer one from.
+    pub fn from_source(
+        source: Box<dyn MediaSource>,
+        hint: Option<Hint>,
+        start_frame: usize,
+        block_frames: usize,
+        _poll_interval: Duration,
+        additional_opts: <Self as Decoder>::AdditionalOpts,
+    ) -> Result<(Self, FileInfo<<Self as Decoder>::FileParams>), OpenError> {
+        let hint = hint.unwrap_or_default();
+
+        // Capture the source's byte length (if known) before it is moved into
+        // the `MediaSourceStream`, in case `n_frames` turns out to be absent
+        // and we need to estimate a frame count from it below.
+        let source_byte_len = source.byte_len();
+
         // Create the media source stream using the boxed media source from above.
         let mss = MediaSourceStream::new(source, Default::default());
 
@@ -95,20 +384,57 @@ impl Decoder for SymphoniaDecoder {
         let track_id = default_track.id;
         let params = default_track.codec_params.clone();
 
-        let num_frames = params.n_frames.ok_or_else(|| OpenError::NoNumFrames)? as usize;
         let sample_rate = params.sample_rate;
+
+        // For gapless playback, skip the encoder's leading delay and hide
+        // its trailing padding from the reported frame count.
+        let (delay, padding) = if additional_opts.gapless {
+            (
+                params.delay.unwrap_or(0) as usize,
+                params.padding.unwrap_or(0) as usize,
+            )
+        } else {
+            (0, 0)
+        };
+
         let mut seek_delta = 0_usize;
+        let target_raw_frame = start_frame + delay;
+
+        // If the container doesn't report `n_frames` directly, try deriving
+        // a count from its duration before falling back to the byte-length
+        // heuristic below: seek as far forward as the reader will go and
+        // read back the landed timestamp, which is expressed in the track's
+        // time base (frames, for audio). A successful probe leaves the
+        // reader near the end of the stream, so the real initial seek
+        // immediately below always runs after one.
+        let time_base_num_frames = if params.n_frames.is_none() {
+            reader
+                .seek(
+                    SymphSeekMode::Coarse,
+                    SeekTo::TimeStamp {
+                        ts: u64::MAX,
+                        track_id,
+                    },
+                )
+                .ok()
+                .map(|res| res.actual_ts as usize)
+        } else {
+            None
+        };
 
-        // Seek the reader to the requested position.
-        if start_frame != 0 {
+        // Seek the reader to the requested position, offset by the leading
+        // encoder delay so frame `0` is the first audible frame. Always run
+        // this when the duration probe above succeeded, since that left the
+        // reader parked at (approximately) the end of the stream.
+        if target_raw_frame != 0 || time_base_num_frames.is_some() {
             let res = reader.seek(
-                SeekMode::Accurate,
+                SymphSeekMode::Accurate,
                 SeekTo::TimeStamp {
-                    ts: start_frame as u64,
+                    ts: target_raw_frame as u64,
                     track_id,
                 },
             )?;
-            seek_delta = start_frame - res.actual_ts as usize;
+            seek_delta = target_raw_frame - res.actual_ts as usize;
             debug!("Found seek delta of {} for initial seek", seek_delta);
         }
 
@@ -123,9 +449,15 @@ impl Decoder for SymphoniaDecoder {
         // will be obtained from the signal spec of the first decoded packet.
         let mut channels = params.channels;
 
-        // Decode the first packet to get the signal specification.
+        // Decode the first packet to get the signal specification. Also keep
+        // its encoded size, in case `n_frames` is absent and we need it to
+        // estimate a total frame count below.
+        let mut last_packet_bytes = 0_usize;
         let (decode_buffer, decode_buffer_len) = loop {
-            match decoder.decode(&reader.next_packet()?) {
+            let packet = reader.next_packet()?;
+            last_packet_bytes = packet.buf().len();
+
+            match decoder.decode(&packet) {
                 Ok(decoded) => {
                     // Get the buffer spec.
                     let spec = *decoded.spec();
@@ -141,7 +473,8 @@ impl Decoder for SymphoniaDecoder {
 
                     let len = decoded.frames();
                     if seek_delta < len {
-                        let decode_buffer: AudioBuffer<f32> = decoded.make_equivalent();
+                        let mut decode_buffer: AudioBuffer<S> = decoded.make_equivalent();
+                        decoded.convert(&mut decode_buffer);
 
                         break (decode_buffer, len);
                     } else {
@@ -157,6 +490,15 @@ impl Decoder for SymphoniaDecoder {
                     // Continue by decoding the next packet.
                     continue;
                 }
+                Err(Error::ResetRequired) => {
+                    // The track/stream parameters changed; get a fresh decoder
+                    // for the new parameters and keep going.
+                    debug!("Decoder reset required, recreating decoder");
+                    let fresh_params =
+                        refresh_codec_params(reader.as_ref(), track_id, decoder.codec_params());
+                    decoder = symphonia::default::get_codecs().make(fresh_params, &decoder_opts)?;
+                    continue;
+                }
                 Err(e) => {
                     // Errors other than decode errors are fatal.
                     return Err(e.into());
@@ -164,10 +506,40 @@ impl Decoder for SymphoniaDecoder {
             }
         };
 
+        // Prefer the container-reported frame count. If it's absent (common
+        // for MP3s and streaming/CBR sources with no seek table), prefer the
+        // duration derived above from the container's time base; failing
+        // that (e.g. the source doesn't support seeking), fall back to
+        // estimating from the source's byte length and the size of the first
+        // packet we decoded above.
+        let (raw_num_frames, num_frames_is_estimate) = match params.n_frames {
+            Some(n) => (n as usize, false),
+            None => match time_base_num_frames {
+                Some(n) => {
+                    debug!("n_frames not reported by container, derived {n} frames from its duration");
+                    (n, true)
+                }
+                None => {
+                    let estimate = source_byte_len
+                        .filter(|_| last_packet_bytes > 0 && decode_buffer_len > 0)
+                        .map(|byte_len| {
+                            let avg_bytes_per_frame =
+                                last_packet_bytes as f64 / decode_buffer_len as f64;
+                            (byte_len as f64 / avg_bytes_per_frame) as usize
+                        })
+                        .unwrap_or(0);
+                    debug!("n_frames not reported by container, estimated {estimate} frames");
+                    (estimate, true)
+                }
+            },
+        };
+        let num_frames = raw_num_frames.saturating_sub(delay + padding);
+
         let metadata = reader.metadata().skip_to_latest().cloned();
         let info = SymphoniaDecoderInfo {
             codec_params: params,
             metadata,
+            num_frames_is_estimate,
         };
         let num_channels = (channels.ok_or_else(|| OpenError::NoNumChannels)?).count();
 
@@ -194,178 +566,13 @@ impl Decoder for SymphoniaDecoder {
 
                 seek_delta: 0,
                 default_track_id: track_id,
+
+                delay,
             },
             file_info,
         ))
     }
 
-    fn seek(&mut self, frame: usize) -> Result<(), Self::FatalError> {
-        if frame >= self.num_frames {
-            // Do nothing if out of range.
-            self.playhead_frame = self.num_frames;
-
-            return Ok(());
-        }
-
-        self.playhead_frame = frame;
-
-        match self.reader.seek(
-            SeekMode::Accurate,
-            SeekTo::TimeStamp {
-                ts: frame as u64,
-                track_id: self.default_track_id,
-            },
-        ) {
-            Ok(res) => {
-                self.seek_delta = frame - res.actual_ts as usize;
-                if self.seek_delta > 0 {
-                    debug!("Found seek delta of {}", frame - res.actual_ts as usize);
-                }
-            }
-            Err(e) => {
-                return Err(e);
-            }
-        }
-
-        self.reset_decode_buffer = true;
-        self.curr_decode_buffer_frame = 0;
-
-        /*
-        let decoder_opts = DecoderOptions {
-            verify: false,
-            ..Default::default()
-        };
-
-        self.decoder.close();
-        self.decoder = symphonia::default::get_codecs()
-            .make(self.decoder.codec_params(), &decoder_opts)?;
-            */
-
-        Ok(())
-    }
-
-    fn decode(&mut self, block: &mut AudioBlock<Self::T>) -> Result<(), Self::FatalError> {
-        if self.playhead_frame >= self.num_frames {
-            // Fill with zeros if reached the end of the file.
-            for ch in block.channels.iter_mut() {
-                ch.fill(Default::default());
-            }
-
-            return Ok(());
-        }
-
-        let mut reached_end_of_file = false;
-
-        let mut block_start_frame = 0;
-        while block_start_frame < self.block_frames {
-            let num_frames_to_cpy = if self.reset_decode_buffer {
-                // Get new data first.
-                self.reset_decode_buffer = false;
-                0
-            } else {
-                // Find the maximum amount of frames that can be copied.
-                (self.block_frames - block_start_frame)
-                    .min(self.decode_buffer_len - self.curr_decode_buffer_frame)
-            };
-
-            if num_frames_to_cpy != 0 {
-                let src_planes = self.decode_buffer.planes();
-                let src_channels = src_planes.planes();
-
-                for (dst_ch, src_ch) in block.channels.iter_mut().zip(src_channels) {
-                    let src_ch_part = &src_ch[self.curr_decode_buffer_frame
-                        ..self.curr_decode_buffer_frame + num_frames_to_cpy];
-                    dst_ch[block_start_frame..block_start_frame + num_frames_to_cpy]
-                        .copy_from_slice(src_ch_part);
-                }
-
-                block_start_frame += num_frames_to_cpy;
-
-                self.curr_decode_buffer_frame += num_frames_to_cpy;
-                if self.curr_decode_buffer_frame >= self.decode_buffer_len {
-                    self.reset_decode_buffer = true;
-                }
-            } else {
-                // Decode the next packet.
-
-                loop {
-                    match self.reader.next_packet() {
-                        Ok(packet) => {
-                            match self.decoder.decode(&packet) {
-                                Ok(decoded) => {
-                                    let seek_delta = self.seek_delta;
-                                    let decoded_frames = decoded.frames();
-                                    if seek_delta < decoded_frames {
-                                        self.seek_delta = 0;
-                                        self.decode_buffer_len = decoded_frames;
-                                        decoded.convert(&mut self.decode_buffer);
-
-                                        self.curr_decode_buffer_frame = seek_delta;
-                                        if seek_delta > 0 {
-                                            debug!("Recovered seek delta of {seek_delta}");
-                                        }
-                                    } else {
-                                        // Continue until we decode back to the desired seek point
-                                        self.seek_delta -= decoded_frames;
-                                        debug!(
-                                            "Skipped {} decoded frames, seek delta is now {}",
-                                            decoded_frames, self.seek_delta
-                                        );
-                                    }
-                                    break;
-                                }
-                                Err(Error::DecodeError(err)) => {
-                                    // Decode errors are not fatal.
-                                    log::warn!("{err}");
-                                    // Continue by decoding the next packet.
-                                    continue;
-                                }
-                                Err(e) => {
-                                    // Errors other than decode errors are fatal.
-                                    return Err(e);
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            if let Error::IoError(io_error) = &e {
-                                if io_error.kind() == std::io::ErrorKind::UnexpectedEof {
-                                    // End of file, stop decoding.
-                                    reached_end_of_file = true;
-                                    block_start_frame = self.block_frames;
-                                    break;
-                                } else {
-                                    return Err(e);
-                                }
-                            } else {
-                                return Err(e);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        if reached_end_of_file {
-            self.playhead_frame = self.num_frames;
-        } else {
-            self.playhead_frame += self.block_frames;
-        }
-
-        Ok(())
-    }
-
-    fn playhead_frame(&self) -> usize {
-        self.playhead_frame
-    }
-}
-
-impl Drop for SymphoniaDecoder {
-    fn drop(&mut self) {
-        let _ = self.decoder.finalize();
-    }
-}
-
-impl SymphoniaDecoder {
     /// Symphonia does metadata oddly. This is more for raw access.
     ///
     /// See [`Metadata`](https://docs.rs/symphonia-core/0.5.2/symphonia_core/meta/struct.Metadata.html).
@@ -387,6 +594,11 @@ pub struct SymphoniaDecoderInfo {
     pub codec_params: CodecParameters,
     /// Metadata information in the file.
     pub metadata: Option<MetadataRevision>,
+    /// `true` if the container didn't report a frame count and
+    /// [`FileInfo::num_frames`](creek_core::FileInfo::num_frames) was instead
+    /// derived from the container's duration or estimated from the source's
+    /// byte length.
+    pub num_frames_is_estimate: bool,
 }
 
 #[cfg(test)]
@@ -419,7 +631,7 @@ mod tests {
                 0,
                 SymphoniaDecoder::DEFAULT_BLOCK_FRAMES,
                 SymphoniaDecoder::DEFAULT_POLL_INTERVAL,
-                (),
+                SymphoniaDecoderOpts::default(),
             );
             match decoder {
                 Ok((_, file_info)) => {
@@ -434,6 +646,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn from_source_cursor() {
+        let bytes = std::fs::read("../test_files/wav_u8_mono.wav").unwrap();
+        let block_frames = 10;
+
+        let mut hint = Hint::new();
+        hint.with_extension("wav");
+
+        let decoder = SymphoniaDecoder::from_source(
+            Box::new(std::io::Cursor::new(bytes)),
+            Some(hint),
+            0,
+            block_frames,
+            SymphoniaDecoder::<f32>::DEFAULT_POLL_INTERVAL,
+            SymphoniaDecoderOpts::default(),
+        );
+
+        let (mut decoder, file_info) = decoder.unwrap();
+        assert_eq!(file_info.num_channels, 1);
+        assert_eq!(file_info.num_frames, 1323000);
+
+        let mut block = AudioBlock::new(1, block_frames);
+        decoder.decode(&mut block).unwrap();
+
+        let first_frame = [
+            0.0, 0.046875, 0.09375, 0.1484375, 0.1953125, 0.2421875, 0.2890625, 0.3359375,
+            0.3828125, 0.421875,
+        ];
+
+        let samples = &block.channels[0];
+        for i in 0..samples.len() {
+            assert!(approx_eq!(f32, first_frame[i], samples[i], ulps = 2));
+        }
+    }
+
     #[test]
     fn decode_first_frame() {
         let block_frames = 10;
@@ -443,7 +690,7 @@ mod tests {
             0,
             block_frames,
             SymphoniaDecoder::DEFAULT_POLL_INTERVAL,
-            (),
+            SymphoniaDecoderOpts::default(),
         );
 
         let (mut decoder, file_info) = decoder.unwrap();
@@ -482,7 +729,7 @@ mod tests {
 
         // Seek to last frame
         decoder
-            .seek(file_info.num_frames - 1 - block_frames)
+            .seek(file_info.num_frames - 1 - block_frames, SeekMode::Accurate)
             .unwrap();
 
         decoder.decode(&mut block).unwrap();
@@ -493,4 +740,145 @@ mod tests {
 
         assert_eq!(decoder.playhead_frame, file_info.num_frames - 1);
     }
+
+    #[test]
+    fn seek_resets_decoder_state() {
+        let block_frames = 10;
+
+        let decoder = SymphoniaDecoder::new(
+            "../test_files/wav_u8_mono.wav".into(),
+            0,
+            block_frames,
+            SymphoniaDecoder::<f32>::DEFAULT_POLL_INTERVAL,
+            SymphoniaDecoderOpts::default(),
+        );
+
+        let (mut decoder, _file_info) = decoder.unwrap();
+
+        let first_frame = [
+            0.0, 0.046875, 0.09375, 0.1484375, 0.1953125, 0.2421875, 0.2890625, 0.3359375,
+            0.3828125, 0.421875,
+        ];
+
+        let mut block = AudioBlock::new(1, block_frames);
+        decoder.decode(&mut block).unwrap();
+        decoder.decode(&mut block).unwrap(); // advance into the second block
+
+        // Seek back to the start and decode again. If `seek` failed to
+        // reset the decoder (or to flush `curr_decode_buffer_frame`/
+        // `reset_decode_buffer`), this would pick up mid-buffer instead of
+        // cleanly re-decoding frame 0.
+        decoder.seek(0, SeekMode::Accurate).unwrap();
+        decoder.decode(&mut block).unwrap();
+
+        let samples = &block.channels[0];
+        for i in 0..samples.len() {
+            assert_approx_eq!(f32, first_frame[i], samples[i], ulps = 2);
+        }
+        assert_eq!(decoder.playhead_frame, block_frames);
+    }
+
+    #[test]
+    fn decode_stops_at_trimmed_num_frames() {
+        let block_frames = 10;
+
+        let decoder = SymphoniaDecoder::new(
+            "../test_files/wav_u8_mono.wav".into(),
+            0,
+            block_frames,
+            SymphoniaDecoder::<f32>::DEFAULT_POLL_INTERVAL,
+            SymphoniaDecoderOpts::default(),
+        );
+
+        let (mut decoder, _file_info) = decoder.unwrap();
+
+        // Simulate gapless trimming landing mid-block, as it would whenever
+        // the trailing padding doesn't happen to end on a block boundary:
+        // pretend the real (trimmed) file ends 3 frames into the second
+        // block, even though the decoder still has real packets to decode
+        // past that point.
+        let trimmed_num_frames = block_frames + 3;
+        decoder.num_frames = trimmed_num_frames;
+
+        let mut block = AudioBlock::new(1, block_frames);
+        decoder.decode(&mut block).unwrap();
+
+        let mut block = AudioBlock::new(1, block_frames);
+        decoder.decode(&mut block).unwrap();
+
+        let second_frame = [
+            0.46875, 0.5078125, 0.5390625, 0.578125, 0.609375, 0.640625, 0.671875, 0.6953125,
+            0.71875, 0.7421875,
+        ];
+
+        let samples = &block.channels[0];
+        for i in 0..3 {
+            assert_approx_eq!(f32, second_frame[i], samples[i], ulps = 2);
+        }
+        for (i, &sample) in samples.iter().enumerate().skip(3) {
+            assert_eq!(sample, 0.0, "sample {i} past the trimmed end should be silent");
+        }
+
+        assert_eq!(decoder.playhead_frame, trimmed_num_frames);
+    }
+
+    #[test]
+    fn decode_first_frame_generic_sample_type() {
+        let block_frames = 10;
+
+        let decoder = SymphoniaDecoder::<i16>::new(
+            "../test_files/wav_u8_mono.wav".into(),
+            0,
+            block_frames,
+            SymphoniaDecoder::<i16>::DEFAULT_POLL_INTERVAL,
+            SymphoniaDecoderOpts::default(),
+        );
+
+        let (mut decoder, _file_info) = decoder.unwrap();
+
+        let mut block = AudioBlock::new(1, block_frames);
+        decoder.decode(&mut block).unwrap();
+
+        let samples = &mut block.channels[0];
+        assert_eq!(samples.len(), block_frames);
+
+        // Same frame as `decode_first_frame`, decoded directly to `i16`
+        // rather than converted down from `f32` by the caller.
+        let first_frame_f32 = [
+            0.0, 0.046875, 0.09375, 0.1484375, 0.1953125, 0.2421875, 0.2890625, 0.3359375,
+            0.3828125, 0.421875,
+        ];
+
+        for i in 0..samples.len() {
+            let expected = (first_frame_f32[i] * i16::MAX as f32).round() as i16;
+            assert!(
+                (samples[i] - expected).abs() <= 1,
+                "sample {} was {}, expected approximately {}",
+                i,
+                samples[i],
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn seek_coarse_lands_at_or_before_requested_frame() {
+        let block_frames = 10;
+
+        let decoder = SymphoniaDecoder::<f32>::new(
+            "../test_files/wav_u8_mono.wav".into(),
+            0,
+            block_frames,
+            SymphoniaDecoder::<f32>::DEFAULT_POLL_INTERVAL,
+            SymphoniaDecoderOpts::default(),
+        );
+
+        let (mut decoder, file_info) = decoder.unwrap();
+
+        let target = file_info.num_frames / 2;
+        let landed = decoder.seek(target, SeekMode::Coarse).unwrap();
+
+        assert!(landed <= target);
+        assert_eq!(decoder.playhead_frame, landed);
+    }
 }